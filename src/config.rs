@@ -0,0 +1,36 @@
+/// Configuration options for the [`query_parser_with`](crate::query_parser_with) middleware.
+///
+/// Use [`QueryParserConfig::default`] to get the same behavior as the plain
+/// [`query_parser`](crate::query_parser), then override only the fields you need.
+#[derive(Debug, Clone)]
+pub struct QueryParserConfig {
+    /// The character used to separate key-value pairs in the query string. Defaults to `&`.
+    /// Set this to `;` to support semicolon-delimited query strings (`a=1;b=2`).
+    pub separator: char,
+
+    /// Whether a literal `+` should be decoded as a space, per the
+    /// `application/x-www-form-urlencoded` convention. Defaults to `true`.
+    pub decode_plus_as_space: bool,
+
+    /// Whether query parameter names should be matched case-insensitively. When enabled, keys
+    /// are lower-cased before being stored, so `?Name=Alice` and `?name=Alice` are equivalent.
+    /// The name passed to `query`, `query_all` or `queries().get` is not lower-cased, so it only
+    /// finds such a key when given in lower case. Defaults to `false`.
+    pub case_insensitive: bool,
+
+    /// The maximum number of query parameters to parse. Once reached, parsing stops and any
+    /// remaining pairs in the query string are ignored, guarding against requests with huge
+    /// numbers of query keys inflating memory usage. `None` means unlimited. Defaults to `None`.
+    pub max_params: Option<usize>,
+}
+
+impl Default for QueryParserConfig {
+    fn default() -> Self {
+        QueryParserConfig {
+            separator: '&',
+            decode_plus_as_space: true,
+            case_insensitive: false,
+            max_params: None,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use std::fmt;
+
+/// The error type returned by [`RequestQueryExt::query_into`](crate::RequestQueryExt::query_into)
+/// when the query string cannot be deserialized into the requested type.
+#[derive(Debug)]
+pub struct QueryError(pub(crate) serde_urlencoded::de::Error);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Routerify-Query: Failed to deserialize the query string: {}", self.0)
+    }
+}
+
+impl std::error::Error for QueryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
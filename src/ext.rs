@@ -1,11 +1,15 @@
-use crate::Query;
+use crate::{form::Form, NestedQuery, Query, QueryError, QueryValue};
 use hyper::Request;
-use std::{collections::HashMap, str::FromStr};
+use serde::de::Deserialize;
+use std::{borrow::Cow, collections::HashMap, str::FromStr};
 
 /// An extension trait which extends the [`hyper::Request`](https://docs.rs/hyper/0.13.5/hyper/struct.Request.html) type with some helpful methods to
 /// access query values from `req` object.
 pub trait RequestQueryExt {
-    /// It returns the parsed queries in a [HashMap](https://doc.rust-lang.org/std/collections/struct.HashMap.html).
+    /// It returns the parsed queries in a [HashMap](https://doc.rust-lang.org/std/collections/struct.HashMap.html),
+    /// where each key maps to every value it was given, in order, so repeated keys such as
+    /// `?tag=rust&tag=hyper` are not lost. Use [`query`](RequestQueryExt::query) for the common
+    /// case of a single-valued key, or [`query_all`](RequestQueryExt::query_all) to get every value.
     ///
     /// # Examples
     ///
@@ -21,8 +25,8 @@ pub trait RequestQueryExt {
     ///     // Access the query values.
     ///     let queries = req.queries();
     ///
-    ///     let user_name = queries.get("username").unwrap();
-    ///     let book_name = queries.get("bookname").unwrap();
+    ///     let user_name = &queries.get("username").unwrap()[0];
+    ///     let book_name = &queries.get("bookname").unwrap()[0];
     ///
     ///     Ok(Response::new(Body::from(format!(
     ///         "User: {}, Book: {}",
@@ -41,9 +45,10 @@ pub trait RequestQueryExt {
     /// }
     /// # run();
     /// ```
-    fn queries(&self) -> &HashMap<String, String>;
+    fn queries(&self) -> &HashMap<String, Vec<String>>;
 
-    /// It returns the query value by a query name.
+    /// It returns the query value by a query name. If the key was repeated in the query string,
+    /// this returns the first value only; use [`query_all`](RequestQueryExt::query_all) to get them all.
     ///
     /// # Examples
     ///
@@ -117,21 +122,290 @@ pub trait RequestQueryExt {
     /// # run();
     /// ```
     fn query_parsed<P: Into<String>, T: FromStr>(&self, query_name: P) -> Option<Result<T, <T as FromStr>::Err>>;
+
+    /// It returns every value given for a query name, in the order they appeared in the query
+    /// string. Useful for array-style query params such as `?tag=rust&tag=hyper&tag=web`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{Body, Request, Response, Server};
+    /// use routerify::{Router, RouterService};
+    /// // Import the query_parser function and the RequestQueryExt trait.
+    /// use routerify_query::{query_parser, RequestQueryExt};
+    /// use std::{convert::Infallible, net::SocketAddr};
+    ///
+    /// // A handler for "/" page. Visit: "/?tag=rust&tag=hyper&tag=web" to see query values.
+    /// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    ///     // Access every value for the "tag" query key.
+    ///     let tags = req.query_all("tag").unwrap();
+    ///
+    ///     Ok(Response::new(Body::from(format!("Tags: {}", tags.join(", ")))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// // Create a router.
+    /// Router::builder()
+    ///   // Attach the query_parser middleware.
+    ///   .middleware(query_parser())
+    ///   .get("/", home_handler)
+    ///   .build()
+    ///   .unwrap()
+    /// }
+    /// # run();
+    /// ```
+    fn query_all<P: Into<String>>(&self, query_name: P) -> Option<&[String]>;
+
+    /// It deserializes the whole query string into a user-defined type `T` in one call, using
+    /// [`serde`](https://docs.rs/serde). This is handy when a handler needs several query values
+    /// at once instead of pulling them out and parsing them one by one with [`query_parsed`](RequestQueryExt::query_parsed).
+    /// Deserialization runs over the same decoded pairs that [`query`](RequestQueryExt::query) and
+    /// [`query_all`](RequestQueryExt::query_all) use, so it honors whichever
+    /// [`QueryParserConfig`](crate::QueryParserConfig) the attached middleware was given (custom
+    /// separator, `+`-as-space, case-insensitivity) instead of always assuming `&`-separated pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{Body, Request, Response, Server};
+    /// use routerify::{Router, RouterService};
+    /// // Import the query_parser function and the RequestQueryExt trait.
+    /// use routerify_query::{query_parser, RequestQueryExt};
+    /// use serde::Deserialize;
+    /// use std::{convert::Infallible, net::SocketAddr};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Params {
+    ///     username: String,
+    ///     page: usize,
+    ///     enjoying: bool,
+    /// }
+    ///
+    /// // A handler for "/" page. Visit: "/?username=Alice&page=7&enjoying=true" to see query values.
+    /// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    ///     // Access the query values as a typed struct.
+    ///     let params: Params = req.query_into().unwrap();
+    ///
+    ///     Ok(Response::new(Body::from(format!(
+    ///         "User: {}, Page: {}, Enjoying: {}",
+    ///         params.username, params.page, params.enjoying
+    ///     ))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// // Create a router.
+    /// Router::builder()
+    ///   // Attach the query_parser middleware.
+    ///   .middleware(query_parser())
+    ///   .get("/", home_handler)
+    ///   .build()
+    ///   .unwrap()
+    /// }
+    /// # run();
+    /// ```
+    fn query_into<T: serde::de::DeserializeOwned>(&self) -> Result<T, QueryError>;
+
+    /// It returns the nested [`QueryValue`] tree built out of bracket-style query parameters,
+    /// e.g. `filter[name]=alice&filter[age]=30`. Unlike the other accessors, this returns `None`
+    /// rather than panicking when the middleware hasn't populated it, since nested parsing is an
+    /// opt-in feature attached via [`query_parser_nested`](crate::query_parser_nested) rather than
+    /// the default [`query_parser`](crate::query_parser).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{Body, Request, Response, Server};
+    /// use routerify::{Router, RouterService};
+    /// // Import the query_parser_nested function and the RequestQueryExt trait.
+    /// use routerify_query::{query_parser_nested, RequestQueryExt};
+    /// use std::{convert::Infallible, net::SocketAddr};
+    ///
+    /// // A handler for "/" page. Visit: "/?filter[name]=alice&filter[age]=30" to see query values.
+    /// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    ///     // Access the nested query values.
+    ///     let nested = req.query_nested().unwrap();
+    ///
+    ///     Ok(Response::new(Body::from(format!("{:?}", nested))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// // Create a router.
+    /// Router::builder()
+    ///   // Attach the query_parser_nested middleware.
+    ///   .middleware(query_parser_nested())
+    ///   .get("/", home_handler)
+    ///   .build()
+    ///   .unwrap()
+    /// }
+    /// # run();
+    /// ```
+    fn query_nested(&self) -> Option<&QueryValue>;
+
+    /// It returns the parsed `application/x-www-form-urlencoded` body fields in a
+    /// [HashMap](https://doc.rust-lang.org/std/collections/struct.HashMap.html), where each key
+    /// maps to every value it was given, in order. Requires the
+    /// [`form_parser`](crate::form_parser) middleware to be attached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{Body, Request, Response, Server};
+    /// use routerify::{Router, RouterService};
+    /// // Import the form_parser function and the RequestQueryExt trait.
+    /// use routerify_query::{form_parser, RequestQueryExt};
+    /// use std::{convert::Infallible, net::SocketAddr};
+    ///
+    /// // A handler for a form POST to "/". Submit a body of "username=Alice&bookname=HarryPotter".
+    /// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    ///     let fields = req.form();
+    ///
+    ///     let user_name = &fields.get("username").unwrap()[0];
+    ///     let book_name = &fields.get("bookname").unwrap()[0];
+    ///
+    ///     Ok(Response::new(Body::from(format!(
+    ///         "User: {}, Book: {}",
+    ///         user_name, book_name
+    ///     ))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// // Create a router.
+    /// Router::builder()
+    ///   // Attach the form_parser middleware.
+    ///   .middleware(form_parser())
+    ///   .post("/", home_handler)
+    ///   .build()
+    ///   .unwrap()
+    /// }
+    /// # run();
+    /// ```
+    fn form(&self) -> &HashMap<String, Vec<String>>;
+
+    /// It returns the first value of a form field by name. If the key was repeated in the body,
+    /// this returns the first value only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{Body, Request, Response, Server};
+    /// use routerify::{Router, RouterService};
+    /// // Import the form_parser function and the RequestQueryExt trait.
+    /// use routerify_query::{form_parser, RequestQueryExt};
+    /// use std::{convert::Infallible, net::SocketAddr};
+    ///
+    /// // A handler for a form POST to "/". Submit a body of "username=Alice".
+    /// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    ///     let user_name = req.form_field("username").unwrap();
+    ///
+    ///     Ok(Response::new(Body::from(format!("User: {}", user_name))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// // Create a router.
+    /// Router::builder()
+    ///   // Attach the form_parser middleware.
+    ///   .middleware(form_parser())
+    ///   .post("/", home_handler)
+    ///   .build()
+    ///   .unwrap()
+    /// }
+    /// # run();
+    /// ```
+    fn form_field<P: Into<String>>(&self, field_name: P) -> Option<&String>;
 }
 
 impl RequestQueryExt for Request<hyper::Body> {
-    fn queries(&self) -> &HashMap<String, String> {
+    fn queries(&self) -> &HashMap<String, Vec<String>> {
         self.extensions()
             .get::<Query>()
-            .map(|q| &q.0)
+            .map(|q| &q.map)
             .expect("Routerify-Query: No parsed queries added to the request object while processing request. Make sure the `query_parser` middleware is attached properly.")
     }
 
     fn query<P: Into<String>>(&self, query_name: P) -> Option<&String> {
-        self.queries().get(&query_name.into())
+        self.queries().get(&query_name.into()).and_then(|values| values.first())
     }
 
     fn query_parsed<P: Into<String>, T: FromStr>(&self, query_name: P) -> Option<Result<T, <T as FromStr>::Err>> {
         self.query(query_name).map(|t| t.parse::<T>())
     }
+
+    fn query_all<P: Into<String>>(&self, query_name: P) -> Option<&[String]> {
+        self.queries().get(&query_name.into()).map(|values| values.as_slice())
+    }
+
+    fn query_into<T: serde::de::DeserializeOwned>(&self) -> Result<T, QueryError> {
+        let pairs = &self
+            .extensions()
+            .get::<Query>()
+            .expect("Routerify-Query: No parsed queries added to the request object while processing request. Make sure the `query_parser` middleware is attached properly.")
+            .pairs;
+
+        let deserializer =
+            serde_urlencoded::Deserializer::new(pairs.iter().map(|(key, value)| (Cow::Borrowed(key.as_str()), Cow::Borrowed(value.as_str()))));
+
+        T::deserialize(deserializer).map_err(QueryError)
+    }
+
+    fn query_nested(&self) -> Option<&QueryValue> {
+        self.extensions().get::<NestedQuery>().map(|nested| &nested.0)
+    }
+
+    fn form(&self) -> &HashMap<String, Vec<String>> {
+        self.extensions()
+            .get::<Form>()
+            .map(|form| &form.0)
+            .expect("Routerify-Query: No parsed form fields added to the request object while processing request. Make sure the `form_parser` middleware is attached properly.")
+    }
+
+    fn form_field<P: Into<String>>(&self, field_name: P) -> Option<&String> {
+        self.form().get(&field_name.into()).and_then(|values| values.first())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Body;
+
+    fn request_with_query(map: HashMap<String, Vec<String>>, pairs: Vec<(String, String)>) -> Request<Body> {
+        let mut req = Request::new(Body::empty());
+        req.extensions_mut().insert(Query { map, pairs });
+        req
+    }
+
+    #[test]
+    fn query_all_returns_every_value_in_order() {
+        let mut map = HashMap::new();
+        map.insert("tag".to_string(), vec!["rust".to_string(), "hyper".to_string()]);
+        let req = request_with_query(map, vec![]);
+
+        assert_eq!(req.query_all("tag"), Some(&["rust".to_string(), "hyper".to_string()][..]));
+        assert_eq!(req.query("tag"), Some(&"rust".to_string()));
+        assert_eq!(req.query_all("missing"), None);
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Params {
+        username: String,
+        page: usize,
+    }
+
+    #[test]
+    fn query_into_deserializes_the_decoded_pairs() {
+        let mut map = HashMap::new();
+        map.insert("username".to_string(), vec!["alice".to_string()]);
+        map.insert("page".to_string(), vec!["7".to_string()]);
+        let pairs = vec![("username".to_string(), "alice".to_string()), ("page".to_string(), "7".to_string())];
+        let req = request_with_query(map, pairs);
+
+        let params: Params = req.query_into().unwrap();
+        assert_eq!(
+            params,
+            Params {
+                username: "alice".to_string(),
+                page: 7,
+            }
+        );
+    }
 }
@@ -0,0 +1,244 @@
+use crate::{parse, QueryParserConfig};
+use bytes::{Bytes, BytesMut};
+use hyper::{body::HttpBody, header::CONTENT_TYPE, Body, Request};
+use routerify::Middleware;
+use std::collections::HashMap;
+
+/// Configuration options for the [`form_parser_with`](crate::form_parser_with) middleware.
+#[derive(Debug, Clone)]
+pub struct FormParserConfig {
+    /// The maximum number of bytes of the body to buffer. A body larger than this is passed
+    /// through with an empty form rather than being parsed, guarding against huge bodies
+    /// inflating the parsed `HashMap` *and* against unbounded memory use while buffering: once
+    /// the limit is crossed, the middleware stops accumulating further bytes, so downstream
+    /// middleware/handlers see only the first `max_body_size` bytes of an oversized body rather
+    /// than the full payload. Bodies at or under the limit are preserved in full. Defaults to 64
+    /// KiB.
+    pub max_body_size: usize,
+}
+
+impl Default for FormParserConfig {
+    fn default() -> Self {
+        FormParserConfig {
+            max_body_size: 64 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Form(pub(crate) HashMap<String, Vec<String>>);
+
+/// Parses an `application/x-www-form-urlencoded` request body and populates it in the `req`
+/// object, giving POST form handlers the same typed-access ergonomics that query handlers get
+/// from [`query_parser`](crate::query_parser). Since this consumes the body, it must run as a
+/// [`Middleware::pre`](routerify::Middleware::pre); the body is buffered (up to
+/// [`FormParserConfig::max_body_size`]), parsed, then handed back to the request so downstream
+/// handlers can still read it. A body larger than `max_body_size` skips form-field parsing and is
+/// truncated to the first `max_body_size` bytes for downstream consumers, bounding how much of an
+/// oversized body is ever held in memory. Requests whose `Content-Type` isn't
+/// `application/x-www-form-urlencoded` are passed through untouched, with an empty form.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{Body, Request, Response, Server};
+/// use routerify::{Router, RouterService};
+/// // Import the form_parser function and the RequestQueryExt trait.
+/// use routerify_query::{form_parser, RequestQueryExt};
+/// use std::{convert::Infallible, net::SocketAddr};
+///
+/// // A handler for a form POST to "/". Submit a body of "username=Alice&bookname=HarryPotter".
+/// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+///     // Access the form field values.
+///     let user_name = req.form_field("username").unwrap();
+///     let book_name = req.form_field("bookname").unwrap();
+///
+///     Ok(Response::new(Body::from(format!(
+///         "User: {}, Book: {}",
+///         user_name, book_name
+///     ))))
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// // Create a router.
+/// Router::builder()
+///   // Attach the form_parser middleware.
+///   .middleware(form_parser())
+///   .post("/", home_handler)
+///   .build()
+///   .unwrap()
+/// }
+/// # run();
+/// ```
+pub fn form_parser<B, E>() -> Middleware<B, E>
+where
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    form_parser_with(FormParserConfig::default())
+}
+
+/// Like [`form_parser`], but lets you customize the maximum buffered body size via
+/// [`FormParserConfig`].
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{Body, Request, Response, Server};
+/// use routerify::{Router, RouterService};
+/// // Import the form_parser_with function and the RequestQueryExt trait.
+/// use routerify_query::{form_parser_with, FormParserConfig, RequestQueryExt};
+/// use std::{convert::Infallible, net::SocketAddr};
+///
+/// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+///     let user_name = req.form_field("username").unwrap();
+///
+///     Ok(Response::new(Body::from(format!("User: {}", user_name))))
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// // Create a router.
+/// Router::builder()
+///   // Attach the form_parser_with middleware, capping the buffered body at 8 KiB.
+///   .middleware(form_parser_with(FormParserConfig { max_body_size: 8 * 1024 }))
+///   .post("/", home_handler)
+///   .build()
+///   .unwrap()
+/// }
+/// # run();
+/// ```
+pub fn form_parser_with<B, E>(config: FormParserConfig) -> Middleware<B, E>
+where
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    Middleware::pre(move |req| form_parser_middleware_handler(req, config.clone()))
+}
+
+async fn form_parser_middleware_handler<E>(mut req: Request<Body>, config: FormParserConfig) -> Result<Request<Body>, E>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    let is_form_urlencoded = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("application/x-www-form-urlencoded"))
+        .unwrap_or(false);
+
+    if !is_form_urlencoded {
+        req.extensions_mut().insert(Form(HashMap::new()));
+        return Ok(req);
+    }
+
+    let (parts, body) = req.into_parts();
+    let (bytes, exceeded_limit) = read_body(body, config.max_body_size).await;
+
+    // `exceeded_limit` means `bytes` was truncated to `max_body_size`, so it no longer reflects
+    // the whole body and isn't safe to parse as form fields.
+    let map = if exceeded_limit {
+        HashMap::new()
+    } else {
+        let body_str = String::from_utf8_lossy(&bytes);
+        parse::parse_query_string(&body_str, &QueryParserConfig::default()).0
+    };
+
+    let mut req = Request::from_parts(parts, Body::from(bytes));
+    req.extensions_mut().insert(Form(map));
+
+    Ok(req)
+}
+
+/// Reads `body` into memory, buffering at most `limit` bytes, and returns the buffered bytes
+/// along with whether `body` was larger than that. Buffering stops once `limit` bytes are held:
+/// the chunk that crosses `limit` is cut at the limit, and the remainder of the stream is still drained so the
+/// connection completes normally, but it is discarded rather than held in memory — so callers get
+/// a bound on memory use regardless of how large the real body is, at the cost of only ever
+/// seeing a `limit`-byte prefix of an oversized body.
+async fn read_body(mut body: Body, limit: usize) -> (Bytes, bool) {
+    let mut buf = BytesMut::new();
+    let mut exceeded_limit = false;
+
+    while let Some(chunk) = body.data().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(_) => break,
+        };
+
+        if exceeded_limit {
+            continue;
+        }
+
+        if buf.len() + chunk.len() > limit {
+            let remaining = limit - buf.len();
+            buf.extend_from_slice(&chunk[..remaining]);
+            exceeded_limit = true;
+            continue;
+        }
+
+        buf.extend_from_slice(&chunk);
+    }
+
+    (buf.freeze(), exceeded_limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_body_bounds_memory_when_the_limit_is_exceeded() {
+        let (mut sender, body) = Body::channel();
+        let limit = 10;
+
+        let send = async move {
+            sender.send_data(Bytes::from_static(b"abcde")).await.unwrap();
+            sender.send_data(Bytes::from_static(b"fghij")).await.unwrap();
+            sender.send_data(Bytes::from_static(b"klmno")).await.unwrap();
+        };
+
+        let (_, (bytes, exceeded_limit)) = tokio::join!(send, read_body(body, limit));
+
+        assert!(exceeded_limit);
+        assert!(bytes.len() <= limit, "buffered {} bytes, expected at most {}", bytes.len(), limit);
+        assert_eq!(bytes, Bytes::from_static(b"abcdefghij"));
+    }
+
+    #[tokio::test]
+    async fn read_body_preserves_a_body_within_the_limit() {
+        let (bytes, exceeded_limit) = read_body(Body::from("username=alice"), 64 * 1024).await;
+
+        assert!(!exceeded_limit);
+        assert_eq!(bytes, Bytes::from_static(b"username=alice"));
+    }
+
+    fn form_request(body: &'static str) -> Request<Body> {
+        Request::builder()
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn form_parser_middleware_handler_parses_a_body_within_the_limit() {
+        let config = FormParserConfig::default();
+        let req = form_parser_middleware_handler::<std::convert::Infallible>(form_request("username=alice"), config)
+            .await
+            .unwrap();
+
+        assert_eq!(req.extensions().get::<Form>().unwrap().0.get("username"), Some(&vec!["alice".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn form_parser_middleware_handler_skips_parsing_but_keeps_a_bounded_body_when_oversized() {
+        let config = FormParserConfig { max_body_size: 4 };
+        let req = form_parser_middleware_handler::<std::convert::Infallible>(form_request("username=alice"), config)
+            .await
+            .unwrap();
+
+        assert!(req.extensions().get::<Form>().unwrap().0.is_empty());
+
+        let bytes = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(bytes, Bytes::from_static(b"user"));
+    }
+}
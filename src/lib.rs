@@ -55,14 +55,34 @@
 use hyper::{body::HttpBody, Request};
 use routerify::Middleware;
 use std::collections::HashMap;
-use url::form_urlencoded;
 
+pub use config::QueryParserConfig;
+pub use error::QueryError;
 pub use ext::RequestQueryExt;
+pub use form::{form_parser, form_parser_with, FormParserConfig};
+pub use nested::QueryValue;
 
+mod config;
+mod error;
 mod ext;
+mod form;
+mod nested;
+mod parse;
 
 #[derive(Debug, Clone)]
-pub(crate) struct Query(pub HashMap<String, String>);
+pub(crate) struct Query {
+    /// Every value for a given query key, in the order they appeared in the query string. A
+    /// repeated key such as `?tag=rust&tag=hyper` collects both values instead of only the last.
+    pub(crate) map: HashMap<String, Vec<String>>,
+    /// The decoded `key=value` pairs, in order, already having gone through whichever
+    /// [`QueryParserConfig`] the middleware was configured with (separator, `+`-as-space,
+    /// case-insensitivity). Kept around so `query_into` can deserialize from the same
+    /// config-aware pairs instead of re-parsing the raw query string with different defaults.
+    pub(crate) pairs: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct NestedQuery(pub(crate) QueryValue);
 
 /// Parses the request query string and populates in the `req` object.
 ///
@@ -104,20 +124,192 @@ where
     B: HttpBody + Send + Sync + Unpin + 'static,
     E: std::error::Error + Send + Sync + Unpin + 'static,
 {
-    Middleware::pre(query_parser_middleware_handler::<E>)
+    query_parser_with(QueryParserConfig::default())
+}
+
+/// Like [`query_parser`], but lets you customize how the query string is parsed via
+/// [`QueryParserConfig`], e.g. to accept a semicolon-delimited query string, disable `+`-as-space
+/// decoding, match keys case-insensitively, or cap the number of parsed parameters.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{Body, Request, Response, Server};
+/// use routerify::{Router, RouterService};
+/// // Import the query_parser_with function and the RequestQueryExt trait.
+/// use routerify_query::{query_parser_with, QueryParserConfig, RequestQueryExt};
+/// use std::{convert::Infallible, net::SocketAddr};
+///
+/// // A handler for "/" page. Visit: "/?username=Alice;bookname=HarryPotter" to see query values.
+/// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+///     // Access the query values.
+///     let user_name = req.query("username").unwrap();
+///     let book_name = req.query("bookname").unwrap();
+///
+///     Ok(Response::new(Body::from(format!(
+///         "User: {}, Book: {}",
+///         user_name, book_name
+///     ))))
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// // Create a router.
+/// Router::builder()
+///   // Attach the query_parser_with middleware, accepting `;` as the pair separator and
+///   // capping the number of parsed parameters at 64.
+///   .middleware(query_parser_with(QueryParserConfig {
+///       separator: ';',
+///       max_params: Some(64),
+///       ..QueryParserConfig::default()
+///   }))
+///   .get("/", home_handler)
+///   .build()
+///   .unwrap()
+/// }
+/// # run();
+/// ```
+pub fn query_parser_with<B, E>(config: QueryParserConfig) -> Middleware<B, E>
+where
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    Middleware::pre(move |req| query_parser_middleware_handler(req, config.clone()))
 }
 
-async fn query_parser_middleware_handler<E>(mut req: Request<hyper::Body>) -> Result<Request<hyper::Body>, E>
+async fn query_parser_middleware_handler<E>(
+    mut req: Request<hyper::Body>,
+    config: QueryParserConfig,
+) -> Result<Request<hyper::Body>, E>
 where
     E: std::error::Error + Send + Sync + Unpin + 'static,
 {
-    let mut q = Query(HashMap::new());
+    let mut q = Query {
+        map: HashMap::new(),
+        pairs: Vec::new(),
+    };
 
     if let Some(query_str) = req.uri().query() {
-        q = Query(form_urlencoded::parse(query_str.as_bytes()).into_owned().collect());
+        let (map, pairs) = parse::parse_query_string(query_str, &config);
+
+        q = Query { map, pairs };
     }
 
     req.extensions_mut().insert(q);
 
     Ok(req)
 }
+
+/// Parses the request query string like [`query_parser`], but additionally understands
+/// bracket-style parameter names, e.g. `filter[name]=alice&filter[age]=30&tags[]=a&tags[]=b`,
+/// building a nested [`QueryValue`] tree out of them. This is an opt-in alternative to
+/// `query_parser`: attach it instead when a route needs structured filters, and read the result
+/// with [`req.query_nested()`](RequestQueryExt::query_nested). The flat accessors (`query`,
+/// `query_all`, `queries`) keep working as usual, since this middleware populates both.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{Body, Request, Response, Server};
+/// use routerify::{Router, RouterService};
+/// // Import the query_parser_nested function and the RequestQueryExt trait.
+/// use routerify_query::{query_parser_nested, RequestQueryExt};
+/// use std::{convert::Infallible, net::SocketAddr};
+///
+/// // A handler for "/" page. Visit: "/?filter[name]=alice&filter[age]=30" to see query values.
+/// async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+///     // Access the nested query values.
+///     let nested = req.query_nested().unwrap();
+///
+///     Ok(Response::new(Body::from(format!("{:?}", nested))))
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// // Create a router.
+/// Router::builder()
+///   // Attach the query_parser_nested middleware.
+///   .middleware(query_parser_nested())
+///   .get("/", home_handler)
+///   .build()
+///   .unwrap()
+/// }
+/// # run();
+/// ```
+pub fn query_parser_nested<B, E>() -> Middleware<B, E>
+where
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    Middleware::pre(query_parser_nested_middleware_handler::<E>)
+}
+
+async fn query_parser_nested_middleware_handler<E>(mut req: Request<hyper::Body>) -> Result<Request<hyper::Body>, E>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut root = QueryValue::Map(HashMap::new());
+
+    if let Some(query_str) = req.uri().query() {
+        let (parsed_map, parsed_pairs) = parse::parse_query_string(query_str, &QueryParserConfig::default());
+        map = parsed_map;
+        pairs = parsed_pairs;
+        root = nested::build_nested(pairs.clone());
+    }
+
+    req.extensions_mut().insert(Query { map, pairs });
+    req.extensions_mut().insert(NestedQuery(root));
+
+    Ok(req)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Body;
+
+    #[tokio::test]
+    async fn query_parser_middleware_handler_respects_config() {
+        let config = QueryParserConfig {
+            separator: ';',
+            max_params: Some(1),
+            ..QueryParserConfig::default()
+        };
+
+        let req = Request::builder()
+            .uri("http://example.com/?a=1;b=2")
+            .body(Body::empty())
+            .unwrap();
+
+        let req = query_parser_middleware_handler::<std::convert::Infallible>(req, config)
+            .await
+            .unwrap();
+
+        let query = req.extensions().get::<Query>().unwrap();
+        assert_eq!(query.map.get("a"), Some(&vec!["1".to_string()]));
+        assert_eq!(query.map.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn query_parser_nested_middleware_handler_builds_a_nested_tree() {
+        let req = Request::builder()
+            .uri("http://example.com/?filter[name]=alice")
+            .body(Body::empty())
+            .unwrap();
+
+        let req = query_parser_nested_middleware_handler::<std::convert::Infallible>(req)
+            .await
+            .unwrap();
+
+        let nested = &req.extensions().get::<NestedQuery>().unwrap().0;
+        match nested {
+            QueryValue::Map(map) => match map.get("filter") {
+                Some(QueryValue::Map(filter)) => {
+                    assert_eq!(filter.get("name"), Some(&QueryValue::String("alice".to_string())));
+                }
+                other => panic!("expected filter to be a map, got {:?}", other),
+            },
+            _ => panic!("expected a map"),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+/// A nested representation of query string values, built by parsing bracket-style parameter
+/// names such as `filter[name]=alice`, `filter[age]=30` or `tags[]=a&tags[]=b`, as used by many
+/// web frameworks and by `serde_qs`.
+///
+/// Access it via [`req.query_nested()`](crate::RequestQueryExt::query_nested) when the
+/// [`query_parser_nested`](crate::query_parser_nested) middleware is attached.
+///
+/// If the same path is used both as a scalar and as a container (e.g. `a=1` and `a[b]=2` in the
+/// same query string), the later pair wins and overwrites whatever was built so far at that path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryValue {
+    /// A plain, unbracketed value, e.g. the `alice` in `filter[name]=alice`.
+    String(String),
+    /// A sequence built from a trailing `[]` segment, e.g. `tags[]=a&tags[]=b`.
+    Seq(Vec<QueryValue>),
+    /// A map built from named bracket segments, e.g. `filter[name]=alice&filter[age]=30`.
+    Map(HashMap<String, QueryValue>),
+}
+
+/// Splits a query key such as `filter[name]` into its path segments `["filter", "name"]`. A
+/// trailing empty segment (from `tags[]`) marks an append-to-sequence. A key with no brackets at
+/// all, e.g. `username`, yields the single segment `["username"]`.
+fn split_key(key: &str) -> Vec<String> {
+    let mut parts = key.split('[');
+    let mut segments = vec![parts.next().unwrap_or("").to_string()];
+
+    for part in parts {
+        segments.push(part.trim_end_matches(']').to_string());
+    }
+
+    segments
+}
+
+fn insert_path(node: &mut QueryValue, segments: &[String], value: String) {
+    let (head, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            *node = QueryValue::String(value);
+            return;
+        }
+    };
+
+    if head.is_empty() {
+        if !matches!(node, QueryValue::Seq(_)) {
+            *node = QueryValue::Seq(Vec::new());
+        }
+
+        let seq = match node {
+            QueryValue::Seq(seq) => seq,
+            _ => unreachable!(),
+        };
+
+        if rest.is_empty() {
+            seq.push(QueryValue::String(value));
+        } else {
+            let mut child = QueryValue::Map(HashMap::new());
+            insert_path(&mut child, rest, value);
+            seq.push(child);
+        }
+    } else {
+        if !matches!(node, QueryValue::Map(_)) {
+            *node = QueryValue::Map(HashMap::new());
+        }
+
+        let map = match node {
+            QueryValue::Map(map) => map,
+            _ => unreachable!(),
+        };
+
+        let child = map.entry(head.clone()).or_insert_with(|| QueryValue::Map(HashMap::new()));
+        insert_path(child, rest, value);
+    }
+}
+
+/// Builds a nested [`QueryValue`] tree out of the decoded `key=value` pairs of a query string.
+///
+/// A pair whose key is empty or starts with `[` (e.g. a bare `=x`, or `[]=x`) has no name to
+/// attach to at the root — the leading `[]`/empty-segment append marker only makes sense nested
+/// under a real key — so such pairs are skipped rather than being treated as "append to the root".
+pub(crate) fn build_nested<I: IntoIterator<Item = (String, String)>>(pairs: I) -> QueryValue {
+    let mut root = QueryValue::Map(HashMap::new());
+
+    for (key, value) in pairs {
+        let segments = split_key(&key);
+
+        if segments.first().map_or(true, |first| first.is_empty()) {
+            continue;
+        }
+
+        insert_path(&mut root, &segments, value);
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_a_flat_scalar() {
+        let root = build_nested(vec![("username".to_string(), "alice".to_string())]);
+
+        match root {
+            QueryValue::Map(map) => {
+                assert_eq!(map.get("username"), Some(&QueryValue::String("alice".to_string())));
+            }
+            _ => panic!("expected a map"),
+        }
+    }
+
+    #[test]
+    fn builds_a_named_nested_map() {
+        let root = build_nested(vec![
+            ("filter[name]".to_string(), "alice".to_string()),
+            ("filter[age]".to_string(), "30".to_string()),
+        ]);
+
+        let filter = match &root {
+            QueryValue::Map(map) => match map.get("filter") {
+                Some(QueryValue::Map(filter)) => filter,
+                other => panic!("expected filter to be a map, got {:?}", other),
+            },
+            _ => panic!("expected a map"),
+        };
+
+        assert_eq!(filter.get("name"), Some(&QueryValue::String("alice".to_string())));
+        assert_eq!(filter.get("age"), Some(&QueryValue::String("30".to_string())));
+    }
+
+    #[test]
+    fn builds_a_sequence_from_trailing_brackets() {
+        let root = build_nested(vec![
+            ("tags[]".to_string(), "a".to_string()),
+            ("tags[]".to_string(), "b".to_string()),
+        ]);
+
+        match &root {
+            QueryValue::Map(map) => match map.get("tags") {
+                Some(QueryValue::Seq(seq)) => {
+                    assert_eq!(
+                        seq,
+                        &vec![QueryValue::String("a".to_string()), QueryValue::String("b".to_string())]
+                    );
+                }
+                other => panic!("expected tags to be a sequence, got {:?}", other),
+            },
+            _ => panic!("expected a map"),
+        }
+    }
+
+    #[test]
+    fn a_later_conflicting_pair_overwrites_an_earlier_one() {
+        let root = build_nested(vec![
+            ("a".to_string(), "1".to_string()),
+            ("a[b]".to_string(), "2".to_string()),
+        ]);
+
+        match &root {
+            QueryValue::Map(map) => match map.get("a") {
+                Some(QueryValue::Map(a)) => {
+                    assert_eq!(a.get("b"), Some(&QueryValue::String("2".to_string())));
+                }
+                other => panic!("expected a to be a map, got {:?}", other),
+            },
+            _ => panic!("expected a map"),
+        }
+    }
+
+    #[test]
+    fn ignores_pairs_with_no_name_instead_of_corrupting_the_root() {
+        let root = build_nested(vec![
+            ("a".to_string(), "1".to_string()),
+            ("".to_string(), "2".to_string()),
+            ("[]".to_string(), "3".to_string()),
+            ("b".to_string(), "4".to_string()),
+        ]);
+
+        match &root {
+            QueryValue::Map(map) => {
+                assert_eq!(map.get("a"), Some(&QueryValue::String("1".to_string())));
+                assert_eq!(map.get("b"), Some(&QueryValue::String("4".to_string())));
+                assert_eq!(map.len(), 2);
+            }
+            _ => panic!("expected a map"),
+        }
+    }
+}
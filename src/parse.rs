@@ -0,0 +1,118 @@
+use crate::QueryParserConfig;
+use std::collections::HashMap;
+
+/// Parses a raw (still percent-encoded) query string according to `config`, returning both a
+/// multi-value map (for the `query`/`query_all` accessors) and the ordered list of decoded pairs
+/// (for `query_into` and the nested parser).
+pub(crate) fn parse_query_string(
+    query_str: &str,
+    config: &QueryParserConfig,
+) -> (HashMap<String, Vec<String>>, Vec<(String, String)>) {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    let mut pairs = Vec::new();
+
+    for raw_pair in query_str.split(config.separator) {
+        if raw_pair.is_empty() {
+            continue;
+        }
+
+        if let Some(max_params) = config.max_params {
+            if pairs.len() >= max_params {
+                break;
+            }
+        }
+
+        let (raw_key, raw_value) = match raw_pair.find('=') {
+            Some(idx) => (&raw_pair[..idx], &raw_pair[idx + 1..]),
+            None => (raw_pair, ""),
+        };
+
+        let mut key = decode_component(raw_key, config.decode_plus_as_space);
+        let value = decode_component(raw_value, config.decode_plus_as_space);
+
+        if config.case_insensitive {
+            key = key.to_lowercase();
+        }
+
+        map.entry(key.clone()).or_insert_with(Vec::new).push(value.clone());
+        pairs.push((key, value));
+    }
+
+    (map, pairs)
+}
+
+fn decode_component(raw: &str, decode_plus_as_space: bool) -> String {
+    if decode_plus_as_space {
+        let replaced = raw.replace('+', " ");
+        percent_encoding::percent_decode_str(&replaced).decode_utf8_lossy().into_owned()
+    } else {
+        percent_encoding::percent_decode_str(raw).decode_utf8_lossy().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ampersand_separated_pairs_by_default() {
+        let (map, pairs) = parse_query_string("a=1&b=2", &QueryParserConfig::default());
+
+        assert_eq!(map.get("a"), Some(&vec!["1".to_string()]));
+        assert_eq!(map.get("b"), Some(&vec!["2".to_string()]));
+        assert_eq!(pairs, vec![("a".to_string(), "1".to_string()), ("b".to_string(), "2".to_string())]);
+    }
+
+    #[test]
+    fn supports_a_custom_separator() {
+        let config = QueryParserConfig {
+            separator: ';',
+            ..QueryParserConfig::default()
+        };
+
+        let (map, _pairs) = parse_query_string("a=1;b=2", &config);
+
+        assert_eq!(map.get("a"), Some(&vec!["1".to_string()]));
+        assert_eq!(map.get("b"), Some(&vec!["2".to_string()]));
+    }
+
+    #[test]
+    fn max_params_stops_parsing_once_reached() {
+        let config = QueryParserConfig {
+            max_params: Some(1),
+            ..QueryParserConfig::default()
+        };
+
+        let (map, pairs) = parse_query_string("a=1&b=2&c=3", &config);
+
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key("a"));
+    }
+
+    #[test]
+    fn case_insensitive_lowercases_keys() {
+        let config = QueryParserConfig {
+            case_insensitive: true,
+            ..QueryParserConfig::default()
+        };
+
+        let (map, _pairs) = parse_query_string("Name=Alice", &config);
+
+        assert_eq!(map.get("name"), Some(&vec!["Alice".to_string()]));
+        assert!(!map.contains_key("Name"));
+    }
+
+    #[test]
+    fn decode_plus_as_space_can_be_disabled() {
+        let (map, _pairs) = parse_query_string("q=a+b", &QueryParserConfig::default());
+        assert_eq!(map.get("q"), Some(&vec!["a b".to_string()]));
+
+        let config = QueryParserConfig {
+            decode_plus_as_space: false,
+            ..QueryParserConfig::default()
+        };
+        let (map, _pairs) = parse_query_string("q=a+b", &config);
+        assert_eq!(map.get("q"), Some(&vec!["a+b".to_string()]));
+    }
+}